@@ -0,0 +1,103 @@
+use ::alsa::mixer::{Mixer, Selem};
+use log::{error, trace};
+use std::error::Error;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+use crate::audio::AudioBackend;
+use crate::config::Source;
+
+pub struct Controller {
+    cards: Vec<String>,
+    tx: Sender<bool>,
+    rx: Receiver<bool>,
+}
+
+impl Controller {
+    pub fn new(source: &Source) -> (Self, Sender<bool>) {
+        let (tx, rx) = mpsc::channel();
+
+        (
+            Controller {
+                cards: parse_cards(source),
+                tx: tx.clone(),
+                rx,
+            },
+            tx,
+        )
+    }
+
+    // Toggle the capture switch of every mixer element that has one, on each card.
+    fn apply(&self, mute: bool) -> Result<(), Box<dyn Error>> {
+        for card in &self.cards {
+            let mixer = Mixer::new(card, false)
+                .map_err(|err| format!("Can't open ALSA mixer for card '{card}': {err}"))?;
+
+            for selem in mixer.iter().filter_map(Selem::new) {
+                if !selem.has_capture_switch() {
+                    continue;
+                }
+                trace!("device source: {:?}", selem.get_id().get_name());
+                selem.set_capture_switch_all(if mute { 0 } else { 1 })?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl AudioBackend for Controller {
+    fn run(
+        &self,
+        _tx_exit: Sender<bool>,
+        _is_paused: Arc<Mutex<bool>>,
+    ) -> Result<(), Box<dyn Error>> {
+        // Apply every requested mute unconditionally: the pause state is
+        // enforced upstream (libinput stops emitting key mutes while paused and
+        // explicitly sends the open/restore transition), matching pulseaudio.
+        loop {
+            if let Ok(mute) = self.rx.recv() {
+                if let Err(err) = self.apply(mute) {
+                    error!("Can't mute devices, ignoring...: {err}");
+                }
+            }
+        }
+    }
+
+    fn set_mute(&self, mute: bool) {
+        if let Err(err) = self.tx.send(mute) {
+            error!("Can't send mute request to alsa backend: {err}");
+        }
+    }
+}
+
+// Resolve the ALSA cards to drive from the `[source].cards` config, falling
+// back to the `default` card when none is configured. These are ALSA card
+// names, distinct from the PulseAudio `targets` description patterns.
+fn parse_cards(source: &Source) -> Vec<String> {
+    if source.cards.is_empty() {
+        vec!["default".to_string()]
+    } else {
+        source.cards.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cards_default() {
+        let source = Source::default();
+        assert_eq!(parse_cards(&source), vec!["default"]);
+    }
+
+    #[test]
+    fn test_parse_cards_from_config() {
+        let source = Source {
+            cards: vec!["hw:1".to_string(), "hw:2".to_string()],
+            ..Source::default()
+        };
+        assert_eq!(parse_cards(&source), vec!["hw:1", "hw:2"]);
+    }
+}
@@ -0,0 +1,42 @@
+use crate::config::Source;
+use crate::{alsa, pulseaudio};
+use std::env;
+use std::error::Error;
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+
+/// A mute backend driving the system microphone.
+///
+/// Implementations own the channel that [`crate::libinput::Controller`] feeds
+/// mute requests into and translate them to whatever sound server is in use,
+/// so the wiring in `main.rs` no longer depends on a specific library.
+pub trait AudioBackend: Send {
+    /// Run the backend event loop until the program exits or errors out.
+    fn run(&self, tx_exit: Sender<bool>, is_paused: Arc<Mutex<bool>>)
+        -> Result<(), Box<dyn Error>>;
+
+    /// Request the microphone be muted (`true`) or unmuted (`false`).
+    fn set_mute(&self, mute: bool);
+}
+
+/// Build the audio backend selected by `PUSH2TALK_BACKEND` (`pulse` by default,
+/// `alsa` to drive ALSA mixer elements directly).
+///
+/// Returns the backend boxed behind [`AudioBackend`] together with the mute
+/// channel sender handed to [`crate::libinput::Controller`].
+pub fn new(source: &Source) -> (Box<dyn AudioBackend>, Sender<bool>) {
+    match parse_backend().as_deref() {
+        Some("alsa") => {
+            let (ctl, tx) = alsa::Controller::new(source);
+            (Box::new(ctl), tx)
+        }
+        _ => {
+            let (ctl, tx) = pulseaudio::Controller::new(source);
+            (Box::new(ctl), tx)
+        }
+    }
+}
+
+fn parse_backend() -> Option<String> {
+    env::var("PUSH2TALK_BACKEND").ok().map(|v| v.to_lowercase())
+}
@@ -0,0 +1,177 @@
+use directories_next::BaseDirs;
+use log::{debug, error};
+use serde::Deserialize;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+/// Persistent configuration loaded from `$XDG_CONFIG_HOME/push2talk/config.toml`.
+///
+/// Every field has a default, so a missing or partial file is valid. The
+/// `PUSH2TALK_KEYBIND` and `PUSH2TALK_SOURCE` environment variables still work
+/// and override the corresponding sections for backward compatibility.
+#[derive(Debug, Default, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    pub keybind: Keybind,
+    #[serde(default)]
+    pub source: Source,
+}
+
+/// The `[keybind]` section: the chord that opens the microphone and how it
+/// behaves.
+#[derive(Debug, Deserialize)]
+pub struct Keybind {
+    /// Keysym names forming the chord, e.g. `["Control_L", "Space"]`.
+    #[serde(default = "default_keys")]
+    pub keys: Vec<String>,
+    /// Activation mode for the chord.
+    #[serde(default)]
+    pub mode: Mode,
+}
+
+/// The `[source]` section: which devices to target. The two lists live in
+/// different namespaces because the backends resolve names differently, so a
+/// config is not portable between them without adjustment.
+#[derive(Debug, Default, Deserialize)]
+pub struct Source {
+    /// PulseAudio source descriptions to mute (substring match, e.g.
+    /// `"Built-in Microphone"`). Empty means every recording source whose
+    /// description does not contain "Easy Effects" (case-insensitive).
+    #[serde(default)]
+    pub targets: Vec<String>,
+    /// ALSA card names passed verbatim to the mixer (e.g. `"default"`,
+    /// `"hw:1"`). Empty means the `default` card.
+    #[serde(default)]
+    pub cards: Vec<String>,
+}
+
+/// How the keybind drives the mute state.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Mode {
+    /// Hold the chord to open the microphone (the historical behavior).
+    #[default]
+    PushToTalk,
+    /// Tap the chord to flip a latched mute state.
+    Toggle,
+    /// Hold the chord to mute an otherwise-open microphone.
+    PushToMute,
+}
+
+impl Default for Keybind {
+    fn default() -> Self {
+        Keybind {
+            keys: default_keys(),
+            mode: Mode::default(),
+        }
+    }
+}
+
+fn default_keys() -> Vec<String> {
+    vec!["Control_L".to_string(), "Space".to_string()]
+}
+
+/// Load the configuration, applying environment-variable overrides on top of
+/// whatever the config file provides (or the built-in defaults if absent).
+pub fn load() -> Config {
+    let mut config = read_config_file().unwrap_or_default();
+    config.apply_env_overrides();
+    debug!("Using configuration: {config:?}");
+    config
+}
+
+impl Config {
+    fn apply_env_overrides(&mut self) {
+        if let Ok(keybind) = env::var("PUSH2TALK_KEYBIND") {
+            self.keybind.keys = keybind.split(',').map(|k| k.trim().to_string()).collect();
+        }
+        if let Ok(source) = env::var("PUSH2TALK_SOURCE") {
+            self.source.targets = source.split(',').map(|s| s.trim().to_string()).collect();
+        }
+        if let Ok(cards) = env::var("PUSH2TALK_ALSA_CARD") {
+            self.source.cards = cards.split(',').map(|s| s.trim().to_string()).collect();
+        }
+    }
+}
+
+fn config_path() -> Option<PathBuf> {
+    let base_dirs = BaseDirs::new()?;
+    let mut path = base_dirs.config_dir().to_path_buf();
+    path.push("push2talk");
+    path.push("config.toml");
+    Some(path)
+}
+
+fn read_config_file() -> Option<Config> {
+    let path = config_path()?;
+    let content = fs::read_to_string(&path).ok()?;
+    match toml::from_str(&content) {
+        Ok(config) => Some(config),
+        Err(err) => {
+            error!("Can't parse config file {path:?}, using defaults: {err}");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config() {
+        let config = Config::default();
+        assert_eq!(config.keybind.keys, vec!["Control_L", "Space"]);
+        assert_eq!(config.keybind.mode, Mode::PushToTalk);
+        assert!(config.source.targets.is_empty());
+    }
+
+    #[test]
+    fn test_parse_full_config() {
+        let config: Config = toml::from_str(
+            r#"
+            [keybind]
+            keys = ["Super_L"]
+            mode = "toggle"
+
+            [source]
+            targets = ["Built-in Microphone", "USB Mic"]
+            cards = ["hw:1"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.keybind.keys, vec!["Super_L"]);
+        assert_eq!(config.keybind.mode, Mode::Toggle);
+        assert_eq!(config.source.targets, vec!["Built-in Microphone", "USB Mic"]);
+        assert_eq!(config.source.cards, vec!["hw:1"]);
+    }
+
+    #[test]
+    fn test_keybind_env_override() {
+        std::env::set_var("PUSH2TALK_KEYBIND", "Control_L,O");
+        std::env::remove_var("PUSH2TALK_SOURCE");
+        let mut config = Config::default();
+        config.apply_env_overrides();
+        assert_eq!(config.keybind.keys, vec!["Control_L", "O"]);
+        std::env::remove_var("PUSH2TALK_KEYBIND");
+    }
+
+    #[test]
+    fn test_source_env_override() {
+        std::env::set_var("PUSH2TALK_SOURCE", "SourceName, Other Mic");
+        let mut config = Config::default();
+        config.apply_env_overrides();
+        assert_eq!(config.source.targets, vec!["SourceName", "Other Mic"]);
+        std::env::remove_var("PUSH2TALK_SOURCE");
+    }
+
+    #[test]
+    fn test_alsa_card_env_override() {
+        std::env::set_var("PUSH2TALK_ALSA_CARD", "hw:1, hw:2");
+        let mut config = Config::default();
+        config.apply_env_overrides();
+        assert_eq!(config.source.cards, vec!["hw:1", "hw:2"]);
+        std::env::remove_var("PUSH2TALK_ALSA_CARD");
+    }
+}
@@ -0,0 +1,121 @@
+use directories_next::BaseDirs;
+use log::{error, info};
+use std::error::Error;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+
+/// Path to the control socket, alongside the lock file in the XDG runtime dir.
+pub fn socket_path() -> Result<PathBuf, Box<dyn Error>> {
+    let base_dirs = BaseDirs::new().ok_or("Cannot find base directories")?;
+    let mut path = PathBuf::from(
+        base_dirs
+            .runtime_dir()
+            .ok_or("Cannot find XDG runtime directory")?,
+    );
+    path.push("push2talk.sock");
+    Ok(path)
+}
+
+/// Connect to a running daemon, send `command`, and print the returned state.
+pub fn send_command(command: &str) -> Result<(), Box<dyn Error>> {
+    let path = socket_path()?;
+    let mut stream = UnixStream::connect(&path)
+        .map_err(|err| format!("Can't reach push2talk daemon on {path:?}: {err}"))?;
+    writeln!(stream, "{command}")?;
+
+    let mut response = String::new();
+    BufReader::new(stream).read_line(&mut response)?;
+    print!("{response}");
+    Ok(())
+}
+
+/// Accept control connections and drive the shared pause state. `tx` is the
+/// audio mute channel; `active_mute`/`last_mute` are the caches shared with
+/// [`crate::libinput::Controller`], letting resume restore the live decision
+/// for the active mode just like the SIGUSR1 path. Returns an error only if
+/// the socket cannot be bound; afterwards failed connections are logged and
+/// the loop keeps accepting.
+pub fn serve(
+    tx: Sender<bool>,
+    is_paused: Arc<Mutex<bool>>,
+    active_mute: Arc<AtomicBool>,
+    last_mute: Arc<AtomicBool>,
+) -> Result<(), Box<dyn Error>> {
+    let path = socket_path()?;
+    // Drop a stale socket left behind by a previous run.
+    let _ = std::fs::remove_file(&path);
+
+    let listener = UnixListener::bind(&path)
+        .map_err(|err| format!("Can't bind control socket {path:?}: {err}"))?;
+    info!("Listening for control commands on {path:?}");
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if let Err(err) = handle_client(stream, &tx, &is_paused, &active_mute, &last_mute) {
+                    error!("Error handling control connection: {err}");
+                }
+            }
+            Err(err) => error!("Control connection failed: {err}"),
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_client(
+    stream: UnixStream,
+    tx: &Sender<bool>,
+    is_paused: &Arc<Mutex<bool>>,
+    active_mute: &Arc<AtomicBool>,
+    last_mute: &Arc<AtomicBool>,
+) -> Result<(), Box<dyn Error>> {
+    let mut reader = BufReader::new(stream);
+    let mut command = String::new();
+    reader.read_line(&mut command)?;
+    let command = command.trim();
+
+    let mut paused = is_paused
+        .lock()
+        .map_err(|err| format!("Deadlock in control handler: {err}"))?;
+
+    let was_paused = *paused;
+    match command {
+        "pause" => *paused = true,
+        "resume" => *paused = false,
+        "toggle" => *paused = !*paused,
+        "status" => {}
+        other => {
+            let mut stream = reader.into_inner();
+            writeln!(stream, "unknown command: {other}")?;
+            return Ok(());
+        }
+    }
+
+    // On a pause-state change, open the mic while paused and restore the live
+    // active-mode decision on resume, updating the shared cache so libinput's
+    // next edge stays in sync.
+    if *paused != was_paused {
+        let target = if *paused {
+            false
+        } else {
+            active_mute.load(Ordering::Relaxed)
+        };
+        if target != last_mute.swap(target, Ordering::Relaxed) {
+            tx.send(target)?;
+        }
+    }
+
+    info!(
+        "Control command '{command}', {}",
+        if *paused { "paused" } else { "running" }
+    );
+
+    let mut stream = reader.into_inner();
+    writeln!(stream, "{}", if *paused { "paused" } else { "running" })?;
+    Ok(())
+}
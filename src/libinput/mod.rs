@@ -4,6 +4,8 @@ use input::{Libinput, LibinputInterface};
 use itertools::Itertools;
 use libc::{O_RDWR, O_WRONLY};
 use log::{debug, info, trace};
+use signal_hook::consts::SIGUSR1;
+use signal_hook::iterator::Signals;
 use std::error::Error;
 use std::fs::{File, OpenOptions};
 use std::io;
@@ -15,92 +17,114 @@ use std::path::Path;
 use std::sync::mpsc::Sender;
 use std::{
     cell::Cell,
-    env,
     sync::{
         atomic::{AtomicBool, Ordering},
-        Arc,
+        Arc, Mutex,
     },
 };
 use xkbcommon::xkb;
 use xkbcommon::xkb::Keysym;
 
+use crate::config::{Keybind, Mode};
+
 pub struct Controller {
     first_key: Keysym,
     first_key_pressed: Cell<bool>,
     second_key: Option<Keysym>,
     second_key_pressed: Cell<bool>,
-    last_mute: Cell<bool>,
+    // Last value pushed onto the mute channel, shared with the control socket
+    // so pause/resume stays coherent across both trigger paths.
+    last_mute: Arc<AtomicBool>,
+    // The live mute decision for the active (non-paused) mode, mirrored here so
+    // the control socket can restore it on resume without touching the Cells.
+    active_mute: Arc<AtomicBool>,
+    mode: Mode,
+    // Whether the chord was fully held on the previous update, used to detect
+    // the press-and-release transition that drives toggle mode.
+    chord_was_held: Cell<bool>,
+    // Latched mute state flipped by each chord tap in toggle mode.
+    latched_mute: Cell<bool>,
     xkb_state: xkb::State,
 }
 
 impl Controller {
-    pub fn new() -> Result<Self, Box<dyn Error>> {
-        let keybind_parsed = parse_keybind()?;
+    pub fn new(
+        keybind: &Keybind,
+        last_mute: Arc<AtomicBool>,
+        active_mute: Arc<AtomicBool>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let keybind_parsed = parse_keybind(&keybind.keys)?;
         validate_keybind(&keybind_parsed)?;
 
         let keybind_names = keybind_parsed
             .iter()
             .map(|k| xkb::keysym_get_name(*k))
             .join(",");
-        debug!("Using key binding: {keybind_names}");
+        debug!("Using key binding: {keybind_names} ({:?} mode)", keybind.mode);
 
         Ok(Controller {
             first_key: keybind_parsed[0],
             first_key_pressed: Cell::new(false),
             second_key: keybind_parsed.get(1).cloned(),
             second_key_pressed: Cell::new(false),
-            last_mute: Cell::new(false),
+            last_mute,
+            active_mute,
+            mode: keybind.mode,
+            chord_was_held: Cell::new(false),
+            // Start muted unless push-to-mute, matching the init state in `main`.
+            latched_mute: Cell::new(keybind.mode != Mode::PushToMute),
             xkb_state: init_xkb_state()?,
         })
     }
 
-    pub fn run(&self, tx: Sender<bool>, sig_pause: Arc<AtomicBool>) -> Result<(), Box<dyn Error>> {
-        // Mute on init
-        tx.send(true)?;
-
+    pub fn run(&self, tx: Sender<bool>, is_paused: Arc<Mutex<bool>>) -> Result<(), Box<dyn Error>> {
+        // The initial mute state is set through the audio backend (see `main`).
         let mut libinput_context = Libinput::new_with_udev(Push2TalkLibinput);
         libinput_context
             .udev_assign_seat("seat0")
             .map_err(|e| format!("Can't connect to libinput on seat0: {e:?}"))?;
 
-        let mut fds = [libc::pollfd {
-            fd: libinput_context.as_raw_fd(),
-            events: libc::POLLIN,
-            revents: 0,
-        }];
-
-        let poll_timeout = 1000;
-        let mut is_running = true;
+        // SIGUSR1 arrives on a self-pipe fd, so it can share the poll set with
+        // libinput instead of needing a separate sleeping thread.
+        let mut signals = Signals::new([SIGUSR1])
+            .map_err(|err| format!("Unable to register SIGUSR1 signal: {err}"))?;
+
+        let mut fds = [
+            libc::pollfd {
+                fd: libinput_context.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            },
+            libc::pollfd {
+                fd: signals.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            },
+        ];
 
         loop {
-            let poll_err = unsafe { libc::poll(fds.as_mut_ptr(), 1, poll_timeout) } < 0;
-            if poll_err {
-                // on pause signal send, libc abort polling and
-                // receive EINTR error
+            // Block indefinitely; either fd becoming readable wakes us with no
+            // polling latency.
+            if unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, -1) } < 0 {
+                // The signal handler itself interrupts poll; just try again.
                 if io::Error::last_os_error().raw_os_error() == Some(libc::EINTR) {
                     continue;
                 }
                 return Err("Unable to poll libinput, aborting".into());
             }
 
-            libinput_context.dispatch()?;
-
-            if sig_pause.swap(false, Ordering::Relaxed) {
-                is_running = !is_running;
-                info!(
-                    "Received SIGUSR1 signal, {}",
-                    if is_running { "resuming" } else { "pausing" }
-                );
-
-                // Toggle mute on pause/resume
-                tx.send(is_running)?;
-
-                // ignore final events that happened just before the resume signal
-                if is_running {
-                    libinput_context.by_ref().for_each(drop);
+            if fds[1].revents & libc::POLLIN != 0 {
+                for _ in signals.pending() {
+                    self.toggle_pause(&tx, &is_paused, &mut libinput_context)?;
                 }
             }
 
+            libinput_context.dispatch()?;
+
+            let is_running = !*is_paused
+                .lock()
+                .map_err(|err| format!("Deadlock in libinput checking pause state: {err}"))?;
+
             for event in libinput_context.by_ref() {
                 if is_running {
                     self.handle(event, tx.clone())?;
@@ -109,6 +133,37 @@ impl Controller {
         }
     }
 
+    // Flip the shared pause state in response to SIGUSR1 and mute accordingly.
+    fn toggle_pause(
+        &self,
+        tx: &Sender<bool>,
+        is_paused: &Arc<Mutex<bool>>,
+        libinput_context: &mut Libinput,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut paused = is_paused
+            .lock()
+            .map_err(|err| format!("Deadlock in handling UNIX signal: {err}"))?;
+        *paused = !*paused;
+        let is_running = !*paused;
+        info!(
+            "Received SIGUSR1 signal, {}",
+            if is_running { "resuming" } else { "pausing" }
+        );
+
+        // Open the mic while paused; on resume restore the live decision for
+        // the active mode rather than assuming push-to-talk.
+        let target = if *paused { false } else { self.active_mute.load(Ordering::Relaxed) };
+        self.send_mute(tx, target)?;
+
+        // Ignore events that happened just before resuming.
+        if is_running {
+            libinput_context.dispatch()?;
+            libinput_context.by_ref().for_each(drop);
+        }
+
+        Ok(())
+    }
+
     fn handle(&self, event: input::Event, tx: Sender<bool>) -> Result<(), Box<dyn Error>> {
         if let input::Event::Keyboard(key_event) = event {
             let keysym = get_keysym(&key_event, &self.xkb_state);
@@ -121,37 +176,63 @@ impl Controller {
 
             self.update(keysym, pressed);
 
+            // Publish the live decision so the control socket can restore it,
+            // then push it (deduplicated) onto the mute channel.
             let should_mute = self.should_mute();
-            if should_mute != self.last_mute.get() {
-                debug!(
-                    "Microphone is {}",
-                    if should_mute { "muted" } else { "unmuted" }
-                );
-                self.last_mute.set(should_mute);
-                tx.send(should_mute)?;
-            }
+            self.active_mute.store(should_mute, Ordering::Relaxed);
+            self.send_mute(&tx, should_mute)?;
         };
 
         Ok(())
     }
 
+    // Push `mute` onto the audio channel only when it differs from the last
+    // value sent, keeping the shared cache in step.
+    fn send_mute(&self, tx: &Sender<bool>, mute: bool) -> Result<(), Box<dyn Error>> {
+        if mute != self.last_mute.swap(mute, Ordering::Relaxed) {
+            debug!("Microphone is {}", if mute { "muted" } else { "unmuted" });
+            tx.send(mute)?;
+        }
+        Ok(())
+    }
+
     fn update(&self, key: Keysym, pressed: bool) {
         match key {
             k if Some(k) == self.second_key => self.second_key_pressed.set(pressed),
             k if k == self.first_key => self.first_key_pressed.set(pressed),
-            _ => {}
+            _ => return,
         }
+
+        // In toggle mode, a full press-and-release of the chord flips the
+        // latched mute state on the release edge.
+        let held = self.chord_held();
+        if self.mode == Mode::Toggle && self.chord_was_held.get() && !held {
+            self.latched_mute.set(!self.latched_mute.get());
+        }
+        self.chord_was_held.set(held);
+    }
+
+    // Whether every key of the chord is currently held down.
+    fn chord_held(&self) -> bool {
+        self.first_key_pressed.get()
+            && (self.second_key.is_none() || self.second_key_pressed.get())
     }
 
     fn should_mute(&self) -> bool {
-        !self.first_key_pressed.get() || self.second_key.is_some() && !self.second_key_pressed.get()
+        match self.mode {
+            // Mic open only while the chord is held.
+            Mode::PushToTalk => !self.chord_held(),
+            // Mic muted only while the chord is held.
+            Mode::PushToMute => self.chord_held(),
+            // Latched state, tapped to flip.
+            Mode::Toggle => self.latched_mute.get(),
+        }
     }
 }
 
-fn parse_keybind() -> Result<Vec<Keysym>, Box<dyn Error>> {
-    let keybind = env::var("PUSH2TALK_KEYBIND")
-        .unwrap_or("Control_L,Space".to_string())
-        .split(',')
+fn parse_keybind(keys: &[String]) -> Result<Vec<Keysym>, Box<dyn Error>> {
+    let keybind = keys
+        .iter()
         .map(|k| xkb::keysym_from_name(k, xkb::KEYSYM_CASE_INSENSITIVE))
         .collect::<Vec<Keysym>>();
 
@@ -217,13 +298,15 @@ impl LibinputInterface for Push2TalkLibinput {
 mod tests {
     use super::*;
 
+    fn names(keys: &[&str]) -> Vec<String> {
+        keys.iter().map(|k| k.to_string()).collect()
+    }
+
     #[test]
     fn test_parse_keybind_default() {
         // Assuming default keybinds are Control_L and Space
-        std::env::remove_var("PUSH2TALK_KEYBIND");
-        let keybind = parse_keybind().unwrap();
+        let keybind = parse_keybind(&names(&["Control_L", "Space"])).unwrap();
         assert_eq!(keybind.len(), 2);
-        // Assuming default keybinds are Control_L and Space
         assert_eq!(
             keybind[0],
             xkb::keysym_from_name("Control_L", xkb::KEYSYM_CASE_INSENSITIVE)
@@ -236,8 +319,7 @@ mod tests {
 
     #[test]
     fn test_parse_keybind_with_2_valid_keys() {
-        std::env::set_var("PUSH2TALK_KEYBIND", "Control_L,O");
-        let keybind = parse_keybind().unwrap();
+        let keybind = parse_keybind(&names(&["Control_L", "O"])).unwrap();
         assert_eq!(keybind.len(), 2);
         assert_eq!(
             keybind[0],
@@ -247,14 +329,70 @@ mod tests {
             keybind[1],
             xkb::keysym_from_name("O", xkb::KEYSYM_CASE_INSENSITIVE)
         );
-        std::env::remove_var("PUSH2TALK_KEYBIND");
     }
 
     #[test]
     fn test_parse_keybind_with_invalid_key() {
-        std::env::set_var("PUSH2TALK_KEYBIND", "InvalidKey");
-        assert!(parse_keybind().is_err());
-        std::env::remove_var("PUSH2TALK_KEYBIND");
+        assert!(parse_keybind(&names(&["InvalidKey"])).is_err());
+    }
+
+    fn controller(mode: Mode) -> Controller {
+        let keybind = Keybind {
+            keys: names(&["Control_L", "Space"]),
+            mode,
+        };
+        let idle = mode != Mode::PushToMute;
+        Controller::new(
+            &keybind,
+            Arc::new(AtomicBool::new(idle)),
+            Arc::new(AtomicBool::new(idle)),
+        )
+        .unwrap()
+    }
+
+    fn press_chord(c: &Controller) {
+        c.update(c.first_key, true);
+        c.update(c.second_key.unwrap(), true);
+    }
+
+    fn release_chord(c: &Controller) {
+        c.update(c.second_key.unwrap(), false);
+        c.update(c.first_key, false);
+    }
+
+    #[test]
+    fn test_push_to_talk_mode() {
+        let c = controller(Mode::PushToTalk);
+        assert!(c.should_mute());
+        press_chord(&c);
+        assert!(!c.should_mute());
+        release_chord(&c);
+        assert!(c.should_mute());
+    }
+
+    #[test]
+    fn test_push_to_mute_mode() {
+        let c = controller(Mode::PushToMute);
+        assert!(!c.should_mute());
+        press_chord(&c);
+        assert!(c.should_mute());
+        release_chord(&c);
+        assert!(!c.should_mute());
+    }
+
+    #[test]
+    fn test_toggle_mode_flips_on_each_tap() {
+        let c = controller(Mode::Toggle);
+        // Starts muted.
+        assert!(c.should_mute());
+        // One tap unmutes.
+        press_chord(&c);
+        release_chord(&c);
+        assert!(!c.should_mute());
+        // Another tap mutes again.
+        press_chord(&c);
+        release_chord(&c);
+        assert!(c.should_mute());
     }
 
     #[test]
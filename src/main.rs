@@ -2,45 +2,46 @@ use clap::Parser;
 use directories_next::BaseDirs;
 use fs2::FileExt;
 use log::{error, info};
-use signal_hook::flag;
 use std::error::Error;
 use std::fs::OpenOptions;
 use std::path::PathBuf;
-use std::process::Command;
-use std::sync::atomic::Ordering;
+use std::sync::atomic::AtomicBool;
 use std::sync::mpsc::Sender;
-use std::sync::{mpsc, Mutex};
-use std::time::Duration;
-use std::{
-    sync::{atomic::AtomicBool, Arc},
-    thread,
-};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
 
+mod alsa;
+mod audio;
+mod config;
+mod control;
 mod libinput;
+mod midi;
 mod pulseaudio;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
-    /// Toggle pause
-    #[arg(short, long)]
-    toggle_pause: bool,
+    /// Control a running daemon over its socket: pause, resume, toggle or status
+    #[arg(value_parser = ["pause", "resume", "toggle", "status"])]
+    command: Option<String>,
+
+    /// List available PulseAudio sources and exit
+    #[arg(long)]
+    list_sources: bool,
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
     // Initialize cli
     let cli = Cli::parse();
 
-    // Send pause signal
-    if cli.toggle_pause {
-        Command::new("pkill")
-            .args(["-SIGUSR1", "-f", "push2talk"])
-            .spawn()
-            .expect("Can't pause push2talk");
-
-        println!("Toggle pause.");
+    // Enumerate sources so users can discover valid [source] targets
+    if cli.list_sources {
+        return pulseaudio::list_sources();
+    }
 
-        return Ok(());
+    // Forward a control command to the running daemon and print the new state
+    if let Some(command) = cli.command.as_deref() {
+        return control::send_command(command);
     }
 
     // Ensure that only one instance run
@@ -52,24 +53,61 @@ fn main() -> Result<(), Box<dyn Error>> {
     // Initialize logging
     setup_logging();
 
+    // Load persistent configuration (env vars still override it)
+    let config = config::load();
+
     let (tx_exit, rx_exit) = mpsc::channel();
 
-    // Register UNIX signals for pause
+    // Shared pause state, driven by SIGUSR1 (in the libinput loop) and the
+    // control socket.
     let is_paused = Arc::new(Mutex::new(false));
-    register_signal(tx_exit.clone(), is_paused.clone())?;
 
-    let (pulseaudio_ctl, tx_libinput) = pulseaudio::Controller::new();
+    let (audio_ctl, tx_libinput) = audio::new(&config.source);
+
+    // Mute on init through the backend-agnostic interface, but respect the
+    // mode: push-to-mute comes up with the mic open, not muted.
+    let idle_mute = config.keybind.mode != config::Mode::PushToMute;
+    audio_ctl.set_mute(idle_mute);
+
+    // Mute caches shared between the libinput loop and the control socket so
+    // pause/resume derives the correct state and stays deduplicated.
+    let last_mute = Arc::new(AtomicBool::new(idle_mute));
+    let active_mute = Arc::new(AtomicBool::new(idle_mute));
+
+    // Accept control commands over the runtime-dir socket
+    let is_paused_control = is_paused.clone();
+    let tx_control = tx_libinput.clone();
+    let active_mute_control = active_mute.clone();
+    let last_mute_control = last_mute.clone();
+    run_in_thread(tx_exit.clone(), "control", move || {
+        control::serve(
+            tx_control,
+            is_paused_control,
+            active_mute_control,
+            last_mute_control,
+        )
+    })?;
 
     // Start set source thread
-    let is_paused_pulseaudio = is_paused.clone();
-    let tx_exit_pulseaudio = tx_exit.clone();
-    run_in_thread(tx_exit.clone(), "pulseaudio", move || {
-        pulseaudio_ctl.run(tx_exit_pulseaudio, is_paused_pulseaudio)
+    let is_paused_audio = is_paused.clone();
+    let tx_exit_audio = tx_exit.clone();
+    run_in_thread(tx_exit.clone(), "audio", move || {
+        audio_ctl.run(tx_exit_audio, is_paused_audio)
     })?;
 
+    // Optionally drive the same mute channel from a MIDI pedal
+    if let Some(midi_ctl) = midi::Controller::new() {
+        let tx_midi = tx_libinput.clone();
+        let is_paused_midi = is_paused.clone();
+        run_in_thread(tx_exit.clone(), "midi", move || {
+            midi_ctl.run(tx_midi, is_paused_midi)
+        })?;
+    }
+
     // Init libinput
     run_in_thread(tx_exit.clone(), "libinput", move || {
-        libinput::Controller::new()?.run(tx_libinput, is_paused)
+        libinput::Controller::new(&config.keybind, last_mute, active_mute)?
+            .run(tx_libinput, is_paused)
     })?;
 
     // Start the application
@@ -117,32 +155,3 @@ where
 
     Ok(())
 }
-
-fn register_signal(
-    tx_exit: Sender<bool>,
-    is_paused: Arc<Mutex<bool>>,
-) -> Result<(), Box<dyn Error>> {
-    let sig_pause = Arc::new(AtomicBool::new(false));
-
-    flag::register(signal_hook::consts::SIGUSR1, Arc::clone(&sig_pause))
-        .map_err(|err| format!("Unable to register SIGUSR1 signal: {err}"))?;
-
-    run_in_thread(tx_exit, "signal_catcher", move || loop {
-        if !sig_pause.swap(false, Ordering::Relaxed) {
-            thread::sleep(Duration::from_millis(250));
-            continue;
-        }
-
-        let mut lock = is_paused
-            .lock()
-            .map_err(|err| format!("Deadlock in handling UNIX signal: {err}"))?;
-
-        *lock = !*lock;
-        info!(
-            "Received SIGUSR1 signal, {}",
-            if *lock { "pausing" } else { "resuming" }
-        );
-    })?;
-
-    Ok(())
-}
@@ -0,0 +1,178 @@
+use log::{error, info, trace};
+use midir::{Ignore, MidiInput};
+use std::env;
+use std::error::Error;
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+// MIDI voice-message status nibbles.
+const NOTE_ON: u8 = 0x90;
+const NOTE_OFF: u8 = 0x80;
+
+pub struct Controller {
+    port: Option<String>,
+    note: u8,
+}
+
+impl Controller {
+    /// Build the controller, or `None` when no `PUSH2TALK_MIDI_*` variable is
+    /// set so the daemon stays inert for users without a MIDI pedal.
+    pub fn new() -> Option<Self> {
+        if env::var_os("PUSH2TALK_MIDI_NOTE").is_none()
+            && env::var_os("PUSH2TALK_MIDI_PORT").is_none()
+        {
+            return None;
+        }
+
+        Some(Controller {
+            port: parse_port(),
+            note: parse_note(),
+        })
+    }
+
+    pub fn run(&self, tx: Sender<bool>, is_paused: Arc<Mutex<bool>>) -> Result<(), Box<dyn Error>> {
+        let mut midi_in = MidiInput::new("push2talk")?;
+        midi_in.ignore(Ignore::None);
+
+        let ports = midi_in.ports();
+        let port = match &self.port {
+            Some(substr) => ports
+                .iter()
+                .find(|p| midi_in.port_name(p).map_or(false, |n| n.contains(substr))),
+            None => ports.first(),
+        }
+        .ok_or("No matching MIDI input port found")?
+        .clone();
+
+        info!(
+            "Using MIDI port: {}",
+            midi_in.port_name(&port).unwrap_or_default()
+        );
+
+        let note = self.note;
+        let _connection = midi_in
+            .connect(
+                &port,
+                "push2talk",
+                move |_stamp, message, _| handle(message, note, &tx, &is_paused),
+                (),
+            )
+            .map_err(|err| format!("Can't connect to MIDI port: {err}"))?;
+
+        // The callback runs on midir's own thread; park here to keep the
+        // connection alive for the lifetime of the daemon.
+        loop {
+            thread::sleep(Duration::from_secs(3600));
+        }
+    }
+}
+
+// Parse a 3-byte channel-voice message and forward a mute request, mirroring
+// the press/release edge logic of `libinput::Controller::handle`.
+fn handle(message: &[u8], note: u8, tx: &Sender<bool>, is_paused: &Arc<Mutex<bool>>) {
+    let [status, data1, data2] = match message {
+        &[s, d1, d2] => [s, d1, d2],
+        _ => return,
+    };
+
+    if data1 != note {
+        return;
+    }
+
+    let pressed = match status & 0xf0 {
+        NOTE_ON if data2 > 0 => true,
+        NOTE_ON | NOTE_OFF => false,
+        _ => return,
+    };
+
+    match is_paused.lock() {
+        Ok(paused) if *paused => return,
+        Ok(_) => {}
+        Err(err) => {
+            error!("Deadlock in midi checking if we are paused: {err}");
+            return;
+        }
+    }
+
+    trace!(
+        "MIDI note {note} {}",
+        if pressed { "pressed" } else { "released" }
+    );
+
+    // Pressing the pedal opens the mic (unmute); releasing mutes again.
+    if let Err(err) = tx.send(!pressed) {
+        error!("Can't forward MIDI event: {err}");
+    }
+}
+
+fn parse_port() -> Option<String> {
+    env::var("PUSH2TALK_MIDI_PORT").ok().filter(|v| !v.is_empty())
+}
+
+fn parse_note() -> u8 {
+    env::var("PUSH2TALK_MIDI_NOTE")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_note_default() {
+        std::env::remove_var("PUSH2TALK_MIDI_NOTE");
+        assert_eq!(parse_note(), 60);
+    }
+
+    #[test]
+    fn test_parse_note_from_env() {
+        std::env::set_var("PUSH2TALK_MIDI_NOTE", "42");
+        assert_eq!(parse_note(), 42);
+        std::env::remove_var("PUSH2TALK_MIDI_NOTE");
+    }
+
+    #[test]
+    fn test_note_on_with_velocity_is_press() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let is_paused = Arc::new(Mutex::new(false));
+        handle(&[NOTE_ON, 60, 100], 60, &tx, &is_paused);
+        // Pressing opens the mic, so mute is requested as `false`.
+        assert!(!rx.recv().unwrap());
+    }
+
+    #[test]
+    fn test_note_on_zero_velocity_is_release() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let is_paused = Arc::new(Mutex::new(false));
+        handle(&[NOTE_ON, 60, 0], 60, &tx, &is_paused);
+        assert!(rx.recv().unwrap());
+    }
+
+    #[test]
+    fn test_note_off_is_release() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let is_paused = Arc::new(Mutex::new(false));
+        handle(&[NOTE_OFF, 60, 0], 60, &tx, &is_paused);
+        assert!(rx.recv().unwrap());
+    }
+
+    #[test]
+    fn test_other_note_is_ignored() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let is_paused = Arc::new(Mutex::new(false));
+        handle(&[NOTE_ON, 61, 100], 60, &tx, &is_paused);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_paused_is_ignored() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let is_paused = Arc::new(Mutex::new(true));
+        handle(&[NOTE_ON, 60, 100], 60, &tx, &is_paused);
+        assert!(rx.try_recv().is_err());
+    }
+}
@@ -9,30 +9,35 @@ use log::{error, trace};
 use std::error::Error;
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::{Arc, Mutex};
+use std::thread;
 use std::time::Duration;
-use std::{env, thread};
+
+use crate::audio::AudioBackend;
+use crate::config::Source;
 
 pub struct Controller {
-    source: Option<String>,
+    sources: Vec<String>,
     tx: Sender<bool>,
     rx: Receiver<bool>,
 }
 
 impl Controller {
-    pub fn new() -> (Self, Sender<bool>) {
+    pub fn new(source: &Source) -> (Self, Sender<bool>) {
         let (tx, rx) = mpsc::channel();
 
         (
             Controller {
-                source: parse_source(),
+                sources: source.targets.clone(),
                 tx: tx.clone(),
                 rx,
             },
             tx,
         )
     }
+}
 
-    pub fn run(
+impl AudioBackend for Controller {
+    fn run(
         &self,
         tx_exit: Sender<bool>,
         is_paused: Arc<Mutex<bool>>,
@@ -85,17 +90,19 @@ impl Controller {
         loop {
             if let Ok(mute) = self.rx.recv() {
                 let mut ctx_volume_controller = context.introspect();
-                let source = self.source.clone();
+                let sources = self.sources.clone();
                 context
                     .introspect()
                     .get_source_info_list(move |devices_list| {
                         if let ListResult::Item(src) = devices_list {
-                            let toggle = match &source {
-                                Some(v) => src.description.as_ref().map_or(false, |d| v == d),
-                                None => src
-                                    .description
+                            let toggle = if sources.is_empty() {
+                                src.description
                                     .as_ref()
-                                    .map_or(true, |d| !d.to_lowercase().contains("easy effects")),
+                                    .map_or(true, |d| !d.to_lowercase().contains("easy effects"))
+                            } else {
+                                src.description.as_ref().map_or(false, |d| {
+                                    sources.iter().any(|s| matches_source(s, d.as_ref()))
+                                })
                             };
                             trace!("device source: {:?}", src.description);
                             if toggle {
@@ -110,26 +117,78 @@ impl Controller {
             }
         }
     }
+
+    fn set_mute(&self, mute: bool) {
+        if let Err(err) = self.tx.send(mute) {
+            error!("Can't send mute request to pulseaudio backend: {err}");
+        }
+    }
 }
 
-fn parse_source() -> Option<String> {
-    env::var_os("PUSH2TALK_SOURCE").map(|v| v.into_string().unwrap_or_default())
+/// Whether a source `description` matches a configured pattern, either as an
+/// exact name or a substring of it. An empty pattern matches nothing, so a
+/// stray empty entry (e.g. a trailing comma) never mutes every source.
+fn matches_source(pattern: &str, description: &str) -> bool {
+    !pattern.is_empty() && description.contains(pattern)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use super::matches_source;
 
     #[test]
-    fn test_parse_source_valid() {
-        std::env::set_var("PUSH2TALK_SOURCE", "SourceName");
-        assert_eq!(parse_source(), Some("SourceName".to_string()));
-        std::env::remove_var("PUSH2TALK_SOURCE");
+    fn test_matches_source_exact() {
+        assert!(matches_source("Built-in Microphone", "Built-in Microphone"));
     }
 
     #[test]
-    fn test_parse_source_empty() {
-        std::env::remove_var("PUSH2TALK_SOURCE");
-        assert_eq!(parse_source(), None);
+    fn test_matches_source_substring() {
+        assert!(matches_source("Microphone", "Built-in Microphone"));
+        assert!(!matches_source("Webcam", "Built-in Microphone"));
+    }
+
+    #[test]
+    fn test_matches_source_empty_pattern() {
+        assert!(!matches_source("", "Built-in Microphone"));
     }
 }
+
+/// Connect to PulseAudio, enumerate every recording source, and print its
+/// name and description so users can discover valid `[source]` targets.
+pub fn list_sources() -> Result<(), Box<dyn Error>> {
+    let mut mainloop = Mainloop::new().ok_or("Failed to create mainloop")?;
+    let mut context =
+        Context::new(&mainloop, "Push2talk").ok_or("Failed to create new context")?;
+
+    context.connect(None, FlagSet::NOFLAGS, None)?;
+    mainloop.start()?;
+
+    loop {
+        match context.get_state() {
+            libpulse_binding::context::State::Ready => break,
+            libpulse_binding::context::State::Failed
+            | libpulse_binding::context::State::Terminated => {
+                return Err("Failed to connect to pulseaudio".into())
+            }
+            _ => thread::sleep(Duration::from_millis(100)),
+        }
+    }
+
+    let (tx, rx) = mpsc::channel();
+    context
+        .introspect()
+        .get_source_info_list(move |devices_list| match devices_list {
+            ListResult::Item(src) => println!(
+                "{}\t{}",
+                src.name.as_deref().unwrap_or("<unnamed>"),
+                src.description.as_deref().unwrap_or("<no description>"),
+            ),
+            ListResult::End | ListResult::Error => {
+                let _ = tx.send(());
+            }
+        });
+
+    rx.recv()?;
+    Ok(())
+}
+